@@ -1,26 +1,496 @@
 //! 2-Party Elliptic curve secret-sharing using Paillier Cryptosystem
 
 use super::slave::{S1, S2, S3};
-use super::{SecretShare, P};
-use curv::arithmetic::{Converter, Modulo};
-use p256::EncodedPoint;
+use super::SecretShare;
+use curv::arithmetic::{BitManipulation, Converter, Modulo, Primes};
 use paillier::*;
+use rand_core::CryptoRngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::marker::PhantomData;
+use zeroize::Zeroizing;
+
+/// Bit length of the Paillier modulus `n = p * q`.
+const PAILLIER_KEY_BITS: usize = 2048;
+
+/// Generates a Paillier keypair using caller-supplied randomness, drawing two
+/// `bit_length / 2`-bit probable primes from `rng` instead of letting the
+/// `paillier` crate reach for the OS RNG internally.
+fn paillier_keypair_with_rng(
+    rng: &mut impl CryptoRngCore,
+    bit_length: usize,
+) -> (EncryptionKey, DecryptionKey) {
+    let p = gen_prime(rng, bit_length / 2);
+    let q = gen_prime(rng, bit_length / 2);
+    let n = &p * &q;
+
+    (EncryptionKey::from(&n), DecryptionKey { p, q })
+}
+
+/// Draws a probable prime of `bit_length` bits from `rng` via rejection
+/// sampling.
+fn gen_prime(rng: &mut impl CryptoRngCore, bit_length: usize) -> BigInt {
+    let num_bytes = bit_length / 8;
+    loop {
+        let mut bytes = vec![0u8; num_bytes];
+        rng.fill_bytes(&mut bytes);
+        // Force the top bit so the candidate has the full bit length, and
+        // the bottom bit so it is odd.
+        bytes[0] |= 0x80;
+        bytes[num_bytes - 1] |= 1;
+
+        let candidate = BigInt::from_bytes(&bytes);
+        if candidate.is_probable_prime(40) {
+            return candidate;
+        }
+    }
+}
+
+/// Slack `ε` added on top of the range bound `ℓ` when sampling the prover's
+/// masks: 256 bits to absorb `e·x` for the 256-bit Fiat-Shamir challenge `e`
+/// (a SHA-256 digest), plus 80 bits of statistical security.
+const RANGE_PROOF_EPSILON: u32 = 256 + 80;
+
+/// Public setup parameters for the CGGMP21-style Paillier range proof
+/// (`Πrange`) attached to every ciphertext in [`M1`].
+///
+/// `n_hat`, `s`, and `t` are an auxiliary RSA modulus and ring-Pedersen
+/// commitment bases generated by the verifier (the slave) and handed to the
+/// master out of band, exactly as in CGGMP21, hence the serde support
+/// alongside `M1`/`M2`/`M3`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeProofParams {
+    /// Auxiliary RSA modulus, `N̂`
+    #[serde(with = "serde_impl::bigint")]
+    n_hat: BigInt,
+    /// Ring-Pedersen base, `s`
+    #[serde(with = "serde_impl::bigint")]
+    s: BigInt,
+    /// Ring-Pedersen base, `t`
+    #[serde(with = "serde_impl::bigint")]
+    t: BigInt,
+    /// Bit length `ℓ` of the range being proven
+    ell: u32,
+}
+
+impl RangeProofParams {
+    pub fn new(n_hat: BigInt, s: BigInt, t: BigInt, ell: u32) -> Self {
+        Self { n_hat, s, t, ell }
+    }
+}
+
+/// A CGGMP21-style `Πrange` proof that a Paillier ciphertext `C` encrypts a
+/// plaintext `x ∈ [-2^ℓ, 2^ℓ]`, without revealing `x`.
+///
+/// This upgrades the premaster-secret protocol from honest-but-curious to
+/// tolerating a cheating master: the slave runs [`RangeProof::verify`] on
+/// every ciphertext in `M1` before operating on it, so an out-of-range
+/// plaintext chosen to exfiltrate the slave's masks is rejected up front.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RangeProof {
+    #[serde(with = "serde_impl::bigint")]
+    s: BigInt,
+    #[serde(with = "serde_impl::bigint")]
+    a: BigInt,
+    #[serde(with = "serde_impl::bigint")]
+    d: BigInt,
+    #[serde(with = "serde_impl::bigint")]
+    z1: BigInt,
+    #[serde(with = "serde_impl::bigint")]
+    z2: BigInt,
+    #[serde(with = "serde_impl::bigint")]
+    z3: BigInt,
+}
+
+/// Error produced when a [`RangeProof`] fails to verify.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeProofError {
+    /// The Paillier ciphertext consistency check failed.
+    CiphertextCheckFailed,
+    /// The ring-Pedersen commitment consistency check failed.
+    CommitmentCheckFailed,
+    /// The prover's response was outside the claimed range.
+    OutOfRange,
+}
+
+impl fmt::Display for RangeProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeProofError::CiphertextCheckFailed => {
+                write!(f, "range proof ciphertext check failed")
+            }
+            RangeProofError::CommitmentCheckFailed => {
+                write!(f, "range proof commitment check failed")
+            }
+            RangeProofError::OutOfRange => write!(f, "range proof response out of range"),
+        }
+    }
+}
+
+impl std::error::Error for RangeProofError {}
+
+impl RangeProof {
+    /// Proves that `ciphertext = Enc(enc_key, plaintext; randomness)` encrypts
+    /// a plaintext in `[-2^ℓ, 2^ℓ]`.
+    fn prove(
+        params: &RangeProofParams,
+        enc_key: &EncryptionKey,
+        plaintext: &BigInt,
+        ciphertext: &BigInt,
+        randomness: &BigInt,
+        rng: &mut impl CryptoRngCore,
+    ) -> Self {
+        let nn = &enc_key.n * &enc_key.n;
+        let range_bound = pow2(params.ell + RANGE_PROOF_EPSILON);
+
+        let alpha = sample_signed(rng, &range_bound);
+        let mu = sample_below(rng, &(&params.n_hat * pow2(params.ell)));
+        let r = sample_below(rng, &enc_key.n);
+        let gamma = sample_below(rng, &(&params.n_hat * &range_bound));
+
+        let s = mod_pow_mul(&params.s, plaintext, &params.t, &mu, &params.n_hat);
+        let a = mod_pow_mul(&(&enc_key.n + 1), &alpha, &r, &enc_key.n, &nn);
+        let d = mod_pow_mul(&params.s, &alpha, &params.t, &gamma, &params.n_hat);
+
+        let e = range_proof_challenge(&enc_key.n, ciphertext, &s, &a, &d);
+
+        let z1 = &alpha + &e * plaintext;
+        let z2 = BigInt::mod_mul(&r, &BigInt::mod_pow(randomness, &e, &enc_key.n), &enc_key.n);
+        let z3 = &gamma + &e * &mu;
+
+        RangeProof { s, a, d, z1, z2, z3 }
+    }
+
+    /// Verifies that `ciphertext` encrypts a plaintext in
+    /// `[-2^(ℓ+ε), 2^(ℓ+ε)]` under `enc_key`.
+    fn verify(
+        &self,
+        params: &RangeProofParams,
+        enc_key: &EncryptionKey,
+        ciphertext: &BigInt,
+    ) -> Result<(), RangeProofError> {
+        let nn = &enc_key.n * &enc_key.n;
+        let e = range_proof_challenge(&enc_key.n, ciphertext, &self.s, &self.a, &self.d);
+
+        let lhs = mod_pow_mul(&(&enc_key.n + 1), &self.z1, &self.z2, &enc_key.n, &nn);
+        let rhs = BigInt::mod_mul(&self.a, &BigInt::mod_pow(ciphertext, &e, &nn), &nn);
+        if lhs != rhs {
+            return Err(RangeProofError::CiphertextCheckFailed);
+        }
+
+        let lhs = mod_pow_mul(&params.s, &self.z1, &params.t, &self.z3, &params.n_hat);
+        let rhs = BigInt::mod_mul(&self.d, &BigInt::mod_pow(&self.s, &e, &params.n_hat), &params.n_hat);
+        if lhs != rhs {
+            return Err(RangeProofError::CommitmentCheckFailed);
+        }
+
+        let range_bound = pow2(params.ell + RANGE_PROOF_EPSILON);
+        if self.z1 < -&range_bound || self.z1 > range_bound {
+            return Err(RangeProofError::OutOfRange);
+        }
+
+        Ok(())
+    }
+}
+
+/// Encrypts `plaintext` under `enc_key` with freshly sampled randomness and
+/// attaches a [`RangeProof`] that it is correctly range-bounded.
+fn encrypt_with_range_proof(
+    enc_key: &EncryptionKey,
+    plaintext: BigInt,
+    range_proof_params: &RangeProofParams,
+    rng: &mut impl CryptoRngCore,
+) -> (BigInt, RangeProof) {
+    let randomness = sample_below(rng, &enc_key.n);
+    let ciphertext: BigInt = Paillier::encrypt_with_chosen_randomness(
+        enc_key,
+        RawPlaintext::from(&plaintext),
+        &Randomness(randomness.clone()),
+    )
+    .into();
+
+    let proof = RangeProof::prove(
+        range_proof_params,
+        enc_key,
+        &plaintext,
+        &ciphertext,
+        &randomness,
+        rng,
+    );
+
+    (ciphertext, proof)
+}
+
+/// Computes `base1^exp1 * base2^exp2 mod modulus`, where `exp1`/`exp2` may be
+/// negative (`alpha`/`z1`, the prover's masked plaintext and its response,
+/// range over `[-2^(ℓ+ε), 2^(ℓ+ε)]`).
+fn mod_pow_mul(base1: &BigInt, exp1: &BigInt, base2: &BigInt, exp2: &BigInt, modulus: &BigInt) -> BigInt {
+    let t1 = mod_pow_signed(base1, exp1, modulus);
+    let t2 = mod_pow_signed(base2, exp2, modulus);
+    BigInt::mod_mul(&t1, &t2, modulus)
+}
+
+/// Computes `base^exp mod modulus`, where `exp` may be negative.
+///
+/// `curv`'s `Modulo::mod_pow` panics on a negative exponent. Every base this
+/// is used with -- `1+N` mod `N²`, and the ring-Pedersen bases `s`/`t` mod
+/// `N̂` -- is a unit in its modulus, so a negative exponent is handled by
+/// inverting the base and exponentiating by its absolute value instead.
+fn mod_pow_signed(base: &BigInt, exp: &BigInt, modulus: &BigInt) -> BigInt {
+    if exp < &BigInt::from(0) {
+        let inverse = BigInt::mod_inv(base, modulus).expect("base must be a unit mod modulus");
+        BigInt::mod_pow(&inverse, &-exp, modulus)
+    } else {
+        BigInt::mod_pow(base, exp, modulus)
+    }
+}
+
+/// Computes `2^bits`.
+fn pow2(bits: u32) -> BigInt {
+    let mut result = BigInt::from(1);
+    let two = BigInt::from(2);
+    for _ in 0..bits {
+        result *= &two;
+    }
+    result
+}
+
+/// Samples uniformly from `[0, bound)`.
+fn sample_below(rng: &mut impl CryptoRngCore, bound: &BigInt) -> BigInt {
+    let num_bytes = bound.bit_length().div_ceil(8);
+    loop {
+        let mut bytes = vec![0u8; num_bytes.max(1)];
+        rng.fill_bytes(&mut bytes);
+        let candidate = BigInt::from_bytes(&bytes);
+        if &candidate < bound {
+            return candidate;
+        }
+    }
+}
+
+/// Samples uniformly from `[-bound, bound]`.
+fn sample_signed(rng: &mut impl CryptoRngCore, bound: &BigInt) -> BigInt {
+    let magnitude = sample_below(rng, &(bound * 2 + 1));
+    magnitude - bound
+}
+
+/// Computes the Fiat-Shamir challenge `e = H(N, C, S, A, D)`.
+fn range_proof_challenge(n: &BigInt, c: &BigInt, s: &BigInt, a: &BigInt, d: &BigInt) -> BigInt {
+    let mut hasher = Sha256::new();
+    for value in [n, c, s, a, d] {
+        hasher.update(value.to_bytes());
+    }
+    BigInt::from_bytes(&hasher.finalize())
+}
+
+/// Curve-specific parameters needed by the 2PC premaster-secret computation.
+///
+/// `SecretShareMasterCore` is generic over this trait so that the masking
+/// arithmetic is not pinned to NIST P-256: implementations supply the field
+/// prime, the byte width of an encoded coordinate, and the exponent `p - 3`
+/// that maps a field element to the inverse of its square.
+pub trait CurveParams {
+    /// The curve's encoded point type, e.g. `p256::EncodedPoint`.
+    type EncodedPoint;
+
+    /// Field prime, as a big-endian hex string.
+    const PRIME_HEX: &'static str;
+
+    /// Byte width of an encoded coordinate.
+    const COORD_BYTES: usize;
+
+    /// Extracts the raw, big-endian `x` coordinate bytes.
+    fn raw_x_bytes(point: &Self::EncodedPoint) -> &[u8];
+
+    /// Extracts the raw, big-endian `y` coordinate bytes.
+    fn raw_y_bytes(point: &Self::EncodedPoint) -> &[u8];
+
+    /// Returns the big-endian `x` coordinate bytes, checked against
+    /// [`COORD_BYTES`](Self::COORD_BYTES).
+    fn x_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        let bytes = Self::raw_x_bytes(point);
+        assert_eq!(bytes.len(), Self::COORD_BYTES, "x-coordinate has unexpected width");
+        bytes
+    }
+
+    /// Returns the big-endian `y` coordinate bytes, checked against
+    /// [`COORD_BYTES`](Self::COORD_BYTES).
+    fn y_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        let bytes = Self::raw_y_bytes(point);
+        assert_eq!(bytes.len(), Self::COORD_BYTES, "y-coordinate has unexpected width");
+        bytes
+    }
+
+    /// Returns the field prime `p`.
+    fn prime() -> BigInt {
+        BigInt::from_hex(Self::PRIME_HEX).unwrap()
+    }
+
+    /// Returns the exponent `p - 3`, which maps a nonzero `v` mod `p` to
+    /// `v^-2`, the inverse of its square.
+    fn inversion_exponent() -> BigInt {
+        Self::prime() - 3
+    }
+}
+
+/// [`CurveParams`] for NIST P-256.
+pub struct P256Params;
+
+impl CurveParams for P256Params {
+    type EncodedPoint = p256::EncodedPoint;
+
+    const PRIME_HEX: &'static str =
+        "FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF";
+    const COORD_BYTES: usize = 32;
+
+    fn raw_x_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        point.x().expect("Invalid point")
+    }
+
+    fn raw_y_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        point.y().expect("Invalid point, or compressed")
+    }
+}
+
+/// [`CurveParams`] for secp256k1.
+pub struct Secp256k1Params;
+
+impl CurveParams for Secp256k1Params {
+    type EncodedPoint = k256::EncodedPoint;
+
+    const PRIME_HEX: &'static str =
+        "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F";
+    const COORD_BYTES: usize = 32;
+
+    fn raw_x_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        point.x().expect("Invalid point")
+    }
+
+    fn raw_y_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        point.y().expect("Invalid point, or compressed")
+    }
+}
+
+/// [`CurveParams`] for the SM2 ShangMi curve.
+pub struct Sm2Params;
+
+impl CurveParams for Sm2Params {
+    type EncodedPoint = sm2::EncodedPoint;
+
+    const PRIME_HEX: &'static str =
+        "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF00000000FFFFFFFFFFFFFFFF";
+    const COORD_BYTES: usize = 32;
+
+    fn raw_x_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        point.x().expect("Invalid point")
+    }
+
+    fn raw_y_bytes(point: &Self::EncodedPoint) -> &[u8] {
+        point.y().expect("Invalid point, or compressed")
+    }
+}
+
+/// Error produced by [`SecretShareMasterCore::verify_consistency`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConsistencyError {
+    /// The reconstructed x-coordinate does not match `point`'s actual
+    /// x-coordinate, meaning the two shares are inconsistent.
+    XCoordinateMismatch,
+}
+
+impl fmt::Display for ConsistencyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConsistencyError::XCoordinateMismatch => {
+                write!(f, "reconstructed x-coordinate does not match the expected point")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConsistencyError {}
+
+/// A `BigInt`'s big-endian bytes, held in a buffer that is zeroized on drop.
+///
+/// `curv::BigInt` does not implement `Zeroize` and does not expose its
+/// internal limbs, so simply overwriting a field with a new `BigInt` (as a
+/// prior version of this wrapper did) only drops the old value -- its
+/// backing allocation is freed untouched, not scrubbed. Instead, premaster-
+/// secret shares, curve coordinates, and intermediate masked values are
+/// stored here as a byte buffer we own, which `Zeroizing` genuinely
+/// overwrites on drop; a `BigInt` is reconstructed from it only for the
+/// instant a computation needs one.
+///
+/// That reconstructed `BigInt` -- and every further `BigInt` derived from it
+/// by the masking arithmetic in [`SecretShareMasterCore::next`] (`x`, `y`,
+/// the decrypted masked values, the reconstructed secret itself) -- is a
+/// plain, un-zeroized value for its lifetime: since `curv::BigInt` cannot be
+/// zeroized in place, this wrapper can only guarantee that the *canonical,
+/// at-rest* copy of a secret is scrubbed, not every transient copy the GMP
+/// arithmetic allocates along the way. Closing that gap fully would mean not
+/// routing secret material through `curv::BigInt` at all.
+pub(crate) struct SecretBigInt(Zeroizing<Vec<u8>>);
+
+impl SecretBigInt {
+    fn new(value: BigInt) -> Self {
+        Self(Zeroizing::new(value.to_bytes()))
+    }
+
+    /// Reconstructs the `BigInt` for use in a computation. The result is a
+    /// plain, un-zeroized value -- see the caveat on [`SecretBigInt`].
+    fn expose(&self) -> BigInt {
+        BigInt::from_bytes(&self.0)
+    }
+
+    /// Consumes the wrapper, returning the `BigInt` it held.
+    fn into_inner(self) -> BigInt {
+        BigInt::from_bytes(&self.0)
+    }
+}
+
+/// A Paillier [`DecryptionKey`]'s primes, held in buffers that are zeroized
+/// on drop. See [`SecretBigInt`] for why `BigInt` itself cannot be zeroized
+/// in place.
+pub(crate) struct SecretDecryptionKey {
+    p: Zeroizing<Vec<u8>>,
+    q: Zeroizing<Vec<u8>>,
+}
+
+impl SecretDecryptionKey {
+    fn new(key: DecryptionKey) -> Self {
+        Self {
+            p: Zeroizing::new(key.p.to_bytes()),
+            q: Zeroizing::new(key.q.to_bytes()),
+        }
+    }
+
+    /// Reconstructs the `DecryptionKey` for use in a single decryption. The
+    /// result is a plain, un-zeroized value -- see the caveat on
+    /// [`SecretBigInt`].
+    fn expose(&self) -> DecryptionKey {
+        DecryptionKey {
+            p: BigInt::from_bytes(&self.p),
+            q: BigInt::from_bytes(&self.q),
+        }
+    }
+}
 
 pub struct Initialized {
     /// X coordinate of master's secret
-    x: BigInt,
+    x: SecretBigInt,
     /// Y coordinate of master's secret
-    y: BigInt,
+    y: SecretBigInt,
 }
 pub struct StepOne;
 pub struct StepTwo {
     /// A * M_A mod p
-    a_masked_mod_p: BigInt,
+    a_masked_mod_p: SecretBigInt,
 }
 pub struct StepThree;
 pub struct Complete {
     /// Master's secret
-    secret: BigInt,
+    secret: SecretBigInt,
 }
 
 pub trait State {}
@@ -30,122 +500,238 @@ impl State for StepTwo {}
 impl State for StepThree {}
 impl State for Complete {}
 
-pub struct SecretShareMasterCore<S>
+pub struct SecretShareMasterCore<S, C>
 where
     S: State,
+    C: CurveParams,
 {
-    /// NIST P-256 Prime
+    /// The curve's field prime
     p: BigInt,
     /// Current state of secret share protocol
     state: S,
     /// Master's Paillier encryption key
     enc_key: EncryptionKey,
     /// Master's Paillier decryption key
-    dec_key: DecryptionKey,
+    dec_key: SecretDecryptionKey,
+    /// The curve this instance is parameterized over
+    curve: PhantomData<C>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct M1 {
     /// Master's encryption key
+    #[serde(with = "serde_impl::enc_key")]
     pub(crate) enc_key: EncryptionKey,
     /// E(x_q)
+    #[serde(with = "serde_impl::bigint")]
     pub(crate) e_x_q: BigInt,
+    /// Proof that `e_x_q` encrypts a correctly-reduced field element
+    pub(crate) e_x_q_proof: RangeProof,
     /// E(-x_q)
+    #[serde(with = "serde_impl::bigint")]
     pub(crate) e_neg_x_q: BigInt,
+    /// Proof that `e_neg_x_q` encrypts a correctly-reduced field element
+    pub(crate) e_neg_x_q_proof: RangeProof,
     /// E(y_q^2)
+    #[serde(with = "serde_impl::bigint")]
     pub(crate) e_y_q_pow_2: BigInt,
+    /// Proof that `e_y_q_pow_2` encrypts a correctly-reduced field element
+    pub(crate) e_y_q_pow_2_proof: RangeProof,
     /// E(-2y_q)
+    #[serde(with = "serde_impl::bigint")]
     pub(crate) e_neg_2_y_q: BigInt,
+    /// Proof that `e_neg_2_y_q` encrypts a correctly-reduced field element
+    pub(crate) e_neg_2_y_q_proof: RangeProof,
+}
+
+impl M1 {
+    /// Verifies the range proof attached to every ciphertext in this
+    /// message. The slave must call this before operating on any of the
+    /// ciphertexts, to tolerate a cheating master.
+    pub fn verify_range_proofs(&self, params: &RangeProofParams) -> Result<(), RangeProofError> {
+        self.e_x_q_proof.verify(params, &self.enc_key, &self.e_x_q)?;
+        self.e_neg_x_q_proof
+            .verify(params, &self.enc_key, &self.e_neg_x_q)?;
+        self.e_y_q_pow_2_proof
+            .verify(params, &self.enc_key, &self.e_y_q_pow_2)?;
+        self.e_neg_2_y_q_proof
+            .verify(params, &self.enc_key, &self.e_neg_2_y_q)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct M2 {
     /// E((T * M_T)^p-3 mod p)
+    #[serde(with = "serde_impl::bigint")]
     pub(crate) e_t_mod_pow: BigInt,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct M3 {
     /// E(A * M_A * B * M_B)
+    #[serde(with = "serde_impl::bigint")]
     pub(crate) e_ab_masked: BigInt,
 }
 
-impl SecretShareMasterCore<Initialized> {
-    pub fn new(point: &EncodedPoint) -> Self {
+/// Wire serialization for the protocol messages.
+///
+/// `BigInt` and `EncryptionKey` have no serde support of their own. An
+/// earlier version of this module hand-rolled a big-endian byte encoding for
+/// `BigInt`, but `curv::arithmetic::Converter`'s `to_bytes`/`from_bytes`
+/// discard the sign, silently corrupting `RangeProof`'s signed `z1`/`z3`
+/// fields on the wire. `paillier::serialize::bigint` is `kzen-paillier`'s own
+/// sign-preserving, decimal-string based serde support, so every `BigInt`
+/// field here is carried with that instead.
+mod serde_impl {
+    use paillier::EncryptionKey;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub(super) use paillier::serialize::bigint;
+
+    /// Serializes a Paillier [`EncryptionKey`] as its modulus `n`, from which
+    /// `nn = n * n` is recomputed on deserialization.
+    pub(super) mod enc_key {
+        use super::*;
+        use paillier::BigInt;
+
+        #[derive(Serialize, Deserialize)]
+        struct EncryptionKeyBytes {
+            #[serde(with = "super::bigint")]
+            n: BigInt,
+        }
+
+        pub(in super::super) fn serialize<S>(
+            value: &EncryptionKey,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            EncryptionKeyBytes { n: value.n.clone() }.serialize(serializer)
+        }
+
+        pub(in super::super) fn deserialize<'de, D>(
+            deserializer: D,
+        ) -> Result<EncryptionKey, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let key = EncryptionKeyBytes::deserialize(deserializer)?;
+            Ok(EncryptionKey::from(&key.n))
+        }
+    }
+}
+
+impl<C> SecretShareMasterCore<Initialized, C>
+where
+    C: CurveParams,
+{
+    pub fn new(point: &C::EncodedPoint) -> Self {
         let (enc_key, dec_key) = Paillier::keypair().keys();
+        Self::from_keys(point, enc_key, dec_key)
+    }
+
+    /// Like [`Self::new`], but threads `rng` into Paillier key generation
+    /// instead of relying on the OS RNG internally, so that seeding with a
+    /// deterministic RNG (e.g. `rand_chacha::ChaChaRng`), together with a
+    /// seeded RNG for [`Self::next`], yields an identical `M1` on every run.
+    /// This makes the state machine reproducible in tests
+    /// and replayable against recorded transcripts.
+    pub fn new_with_rng(point: &C::EncodedPoint, rng: &mut impl CryptoRngCore) -> Self {
+        let (enc_key, dec_key) = paillier_keypair_with_rng(rng, PAILLIER_KEY_BITS);
+        Self::from_keys(point, enc_key, dec_key)
+    }
+
+    fn from_keys(point: &C::EncodedPoint, enc_key: EncryptionKey, dec_key: DecryptionKey) -> Self {
         Self {
             state: Initialized {
-                x: BigInt::from_bytes(point.x().expect("Invalid point")),
-                y: BigInt::from_bytes(point.y().expect("Invalid point, or compressed")),
+                x: SecretBigInt::new(BigInt::from_bytes(C::x_bytes(point))),
+                y: SecretBigInt::new(BigInt::from_bytes(C::y_bytes(point))),
             },
-            p: BigInt::from_hex(P).unwrap(),
+            p: C::prime(),
             enc_key,
-            dec_key,
+            dec_key: SecretDecryptionKey::new(dec_key),
+            curve: PhantomData,
         }
     }
 
-    pub fn next(self) -> (M1, SecretShareMasterCore<StepOne>) {
+    pub fn next(
+        self,
+        range_proof_params: &RangeProofParams,
+        rng: &mut impl CryptoRngCore,
+    ) -> (M1, SecretShareMasterCore<StepOne, C>) {
+        let x = self.state.x.expose();
+        let y = self.state.y.expose();
+
         // Computes E(x_q)
-        let e_x_q: BigInt =
-            Paillier::encrypt(&self.enc_key, RawPlaintext::from(&self.state.x)).into();
+        let (e_x_q, e_x_q_proof) =
+            encrypt_with_range_proof(&self.enc_key, x.clone(), range_proof_params, rng);
 
         // Computes E(-x_q)
-        let e_neg_x_q: BigInt = Paillier::encrypt(
+        let (e_neg_x_q, e_neg_x_q_proof) = encrypt_with_range_proof(
             &self.enc_key,
-            RawPlaintext::from(BigInt::mod_sub(&self.p, &self.state.x, &self.p)),
-        )
-        .into();
+            BigInt::mod_sub(&self.p, &x, &self.p),
+            range_proof_params,
+            rng,
+        );
 
         // Computes E(y_q^2)
-        let e_y_q_pow_2: BigInt = Paillier::encrypt(
+        let (e_y_q_pow_2, e_y_q_pow_2_proof) = encrypt_with_range_proof(
             &self.enc_key,
-            RawPlaintext::from(BigInt::mod_pow(
-                &self.state.y,
-                &BigInt::from(2_u16),
-                &self.p,
-            )),
-        )
-        .into();
+            BigInt::mod_pow(&y, &BigInt::from(2_u16), &self.p),
+            range_proof_params,
+            rng,
+        );
 
         // Computes E(-2y_q)
-        let e_neg_2_y_q: BigInt = Paillier::encrypt(
+        let (e_neg_2_y_q, e_neg_2_y_q_proof) = encrypt_with_range_proof(
             &self.enc_key,
-            RawPlaintext::from(BigInt::mod_sub(&self.p, &(2 * &self.state.y), &self.p)),
-        )
-        .into();
+            BigInt::mod_sub(&self.p, &(2 * &y), &self.p),
+            range_proof_params,
+            rng,
+        );
 
         (
             M1 {
                 enc_key: self.enc_key.clone(),
                 e_x_q,
+                e_x_q_proof,
                 e_neg_x_q,
+                e_neg_x_q_proof,
                 e_y_q_pow_2,
+                e_y_q_pow_2_proof,
                 e_neg_2_y_q,
+                e_neg_2_y_q_proof,
             },
             SecretShareMasterCore {
                 state: StepOne,
                 enc_key: self.enc_key,
                 dec_key: self.dec_key,
                 p: self.p,
+                curve: PhantomData,
             },
         )
     }
 }
 
-impl SecretShareMasterCore<StepOne> {
-    pub fn next(self, s: S1) -> (M2, SecretShareMasterCore<StepTwo>) {
+impl<C> SecretShareMasterCore<StepOne, C>
+where
+    C: CurveParams,
+{
+    pub fn next(self, s: S1) -> (M2, SecretShareMasterCore<StepTwo, C>) {
         // Computes A * M_A mod p
         let a_masked: BigInt =
-            Paillier::decrypt(&self.dec_key, RawCiphertext::from(s.e_a_masked)).into();
-        let a_masked_mod_p = BigInt::mod_sub(&a_masked, &s.n_a_mod_p, &self.p);
+            Paillier::decrypt(&self.dec_key.expose(), RawCiphertext::from(s.e_a_masked)).into();
+        let a_masked_mod_p = SecretBigInt::new(BigInt::mod_sub(&a_masked, &s.n_a_mod_p, &self.p));
 
         // Computes T * M_T mod p
         let t_masked: BigInt =
-            Paillier::decrypt(&self.dec_key, RawCiphertext::from(s.e_t_masked)).into();
+            Paillier::decrypt(&self.dec_key.expose(), RawCiphertext::from(s.e_t_masked)).into();
         let t_masked_mod_p = BigInt::mod_sub(&t_masked, &s.n_t_mod_p, &self.p);
 
         // Computes E((T * M_T)^p-3 mod p)
-        let t_mod_pow = BigInt::mod_pow(&t_masked_mod_p, &(&self.p - 3), &self.p);
+        let t_mod_pow = BigInt::mod_pow(&t_masked_mod_p, &C::inversion_exponent(), &self.p);
         let e_t_mod_pow: BigInt =
             Paillier::encrypt(&self.enc_key, RawPlaintext::from(t_mod_pow)).into();
 
@@ -156,16 +742,20 @@ impl SecretShareMasterCore<StepOne> {
                 enc_key: self.enc_key,
                 dec_key: self.dec_key,
                 p: self.p,
+                curve: PhantomData,
             },
         )
     }
 }
 
-impl SecretShareMasterCore<StepTwo> {
-    pub fn next(self, s: S2) -> (M3, SecretShareMasterCore<StepThree>) {
+impl<C> SecretShareMasterCore<StepTwo, C>
+where
+    C: CurveParams,
+{
+    pub fn next(self, s: S2) -> (M3, SecretShareMasterCore<StepThree, C>) {
         // Computes B * M_B mod p
         let b_masked: BigInt =
-            Paillier::decrypt(&self.dec_key, RawCiphertext::from(s.e_b_masked)).into();
+            Paillier::decrypt(&self.dec_key.expose(), RawCiphertext::from(s.e_b_masked)).into();
         let b_masked_mod_p = BigInt::mod_sub(&b_masked, &s.n_b_mod_p, &self.p);
 
         // Computes E(A * M_A * B * M_B)
@@ -173,7 +763,7 @@ impl SecretShareMasterCore<StepTwo> {
             &self.enc_key,
             RawPlaintext::from(BigInt::mod_mul(
                 &b_masked_mod_p,
-                &self.state.a_masked_mod_p,
+                &self.state.a_masked_mod_p.expose(),
                 &self.p,
             )),
         )
@@ -186,30 +776,213 @@ impl SecretShareMasterCore<StepTwo> {
                 enc_key: self.enc_key,
                 dec_key: self.dec_key,
                 p: self.p,
+                curve: PhantomData,
             },
         )
     }
 }
 
-impl SecretShareMasterCore<StepThree> {
-    pub fn next(self, s: S3) -> SecretShareMasterCore<Complete> {
+impl<C> SecretShareMasterCore<StepThree, C>
+where
+    C: CurveParams,
+{
+    pub fn next(self, s: S3) -> SecretShareMasterCore<Complete, C> {
         // Computes master's secret, s_p
         let pms_masked: BigInt =
-            Paillier::decrypt(&self.dec_key, RawCiphertext::from(s.e_pms_masked)).into();
+            Paillier::decrypt(&self.dec_key.expose(), RawCiphertext::from(s.e_pms_masked)).into();
 
         SecretShareMasterCore {
             state: Complete {
-                secret: pms_masked % &self.p,
+                secret: SecretBigInt::new(pms_masked % &self.p),
             },
             enc_key: self.enc_key,
             dec_key: self.dec_key,
             p: self.p,
+            curve: PhantomData,
         }
     }
 }
 
-impl SecretShareMasterCore<Complete> {
+impl<C> SecretShareMasterCore<Complete, C>
+where
+    C: CurveParams,
+{
     pub fn secret(self) -> SecretShare {
-        self.state.secret
+        self.state.secret.into_inner()
+    }
+
+    /// Checks that this share, combined with the slave's corresponding
+    /// `slave_share`, reconstructs `point`'s actual x-coordinate mod `p`.
+    ///
+    /// An earlier version of this check instead verified that the
+    /// reconstructed x satisfied the curve equation `y² = x³ + a·x + b
+    /// (mod p)` for `point`'s y-coordinate. That cubic has up to three roots
+    /// mod `p`, so it only proved "x is *some* valid curve x-coordinate for
+    /// this y", not that x is `point`'s x-coordinate -- a masking-arithmetic
+    /// bug landing on a different root would still pass. Since `point`'s
+    /// real x-coordinate is already available via `C::x_bytes`, comparing
+    /// directly is both simpler and fully conclusive.
+    pub fn verify_consistency(
+        &self,
+        slave_share: &BigInt,
+        point: &C::EncodedPoint,
+    ) -> Result<(), ConsistencyError> {
+        let x = BigInt::mod_add(&self.state.secret.expose(), slave_share, &self.p);
+        let expected_x = BigInt::from_bytes(C::x_bytes(point)) % &self.p;
+
+        if x == expected_x {
+            Ok(())
+        } else {
+            Err(ConsistencyError::XCoordinateMismatch)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `M1` carries every kind of wire-serialized field the protocol sends
+    /// (`BigInt`, `EncryptionKey`, `RangeProof`), so a round-trip here exercises
+    /// `serde_impl::bigint` and `serde_impl::enc_key` end to end.
+    #[test]
+    fn m1_round_trips_through_serde() {
+        let (enc_key, _dec_key) = Paillier::keypair().keys();
+        let range_proof = RangeProof {
+            s: BigInt::from(1),
+            a: BigInt::from(2),
+            d: BigInt::from(3),
+            z1: BigInt::from(-4),
+            z2: BigInt::from(5),
+            z3: BigInt::from(6),
+        };
+        let m1 = M1 {
+            enc_key,
+            e_x_q: BigInt::from(7),
+            e_x_q_proof: range_proof.clone(),
+            e_neg_x_q: BigInt::from(8),
+            e_neg_x_q_proof: range_proof.clone(),
+            e_y_q_pow_2: BigInt::from(9),
+            e_y_q_pow_2_proof: range_proof.clone(),
+            e_neg_2_y_q: BigInt::from(10),
+            e_neg_2_y_q_proof: range_proof,
+        };
+
+        let json = serde_json::to_string(&m1).expect("M1 should serialize");
+        let decoded: M1 = serde_json::from_str(&json).expect("M1 should deserialize");
+
+        assert_eq!(decoded.enc_key.n, m1.enc_key.n);
+        assert_eq!(decoded.e_x_q, m1.e_x_q);
+        assert_eq!(decoded.e_neg_x_q, m1.e_neg_x_q);
+        assert_eq!(decoded.e_y_q_pow_2, m1.e_y_q_pow_2);
+        assert_eq!(decoded.e_neg_2_y_q, m1.e_neg_2_y_q);
+        assert_eq!(decoded.e_x_q_proof.z1, m1.e_x_q_proof.z1);
+    }
+
+    /// Samples a P-256 point deterministically from `rng`, for use as the
+    /// `point` argument in tests below.
+    fn sample_p256_point(rng: &mut impl CryptoRngCore) -> p256::EncodedPoint {
+        use p256::elliptic_curve::sec1::ToEncodedPoint;
+
+        let secret_key = p256::SecretKey::random(rng);
+        secret_key.public_key().to_encoded_point(false)
+    }
+
+    /// Seeding [`SecretShareMasterCore::new_with_rng`] with two identically
+    /// seeded `ChaChaRng`s should produce the same Paillier key, since
+    /// `paillier_keypair_with_rng` draws all of its randomness from `rng`
+    /// rather than the OS RNG.
+    #[test]
+    fn new_with_rng_is_deterministic() {
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+
+        let point = sample_p256_point(&mut ChaChaRng::seed_from_u64(7));
+
+        let mut rng1 = ChaChaRng::seed_from_u64(42);
+        let master1 =
+            SecretShareMasterCore::<Initialized, P256Params>::new_with_rng(&point, &mut rng1);
+
+        let mut rng2 = ChaChaRng::seed_from_u64(42);
+        let master2 =
+            SecretShareMasterCore::<Initialized, P256Params>::new_with_rng(&point, &mut rng2);
+
+        assert_eq!(master1.enc_key.n, master2.enc_key.n);
+        assert_eq!(master1.dec_key.p, master2.dec_key.p);
+        assert_eq!(master1.dec_key.q, master2.dec_key.q);
+    }
+
+    /// Builds small, test-only `RangeProofParams` and a Paillier key: the
+    /// protocol's real `PAILLIER_KEY_BITS`/`ell` are overkill for a unit test
+    /// and would make it unreasonably slow.
+    fn test_range_proof_fixture(
+        rng: &mut impl CryptoRngCore,
+    ) -> (EncryptionKey, RangeProofParams) {
+        let (enc_key, _dec_key) = paillier_keypair_with_rng(rng, 256);
+        let n_hat = &gen_prime(rng, 128) * &gen_prime(rng, 128);
+        let params = RangeProofParams::new(n_hat, BigInt::from(4), BigInt::from(9), 64);
+        (enc_key, params)
+    }
+
+    #[test]
+    fn range_proof_round_trips() {
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+
+        let mut rng = ChaChaRng::seed_from_u64(13);
+        let (enc_key, params) = test_range_proof_fixture(&mut rng);
+
+        let (ciphertext, proof) =
+            encrypt_with_range_proof(&enc_key, BigInt::from(12345), &params, &mut rng);
+
+        assert!(proof.verify(&params, &enc_key, &ciphertext).is_ok());
+    }
+
+    #[test]
+    fn range_proof_rejects_a_tampered_response() {
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+
+        let mut rng = ChaChaRng::seed_from_u64(14);
+        let (enc_key, params) = test_range_proof_fixture(&mut rng);
+
+        let (ciphertext, mut proof) =
+            encrypt_with_range_proof(&enc_key, BigInt::from(12345), &params, &mut rng);
+        proof.z1 = &proof.z1 + BigInt::from(1);
+
+        assert!(proof.verify(&params, &enc_key, &ciphertext).is_err());
+    }
+
+    /// Builds a `Complete`-state `SecretShareMasterCore` directly, so the
+    /// rest of the protocol (Paillier round-trips, `S1`/`S2`/`S3`) doesn't
+    /// need to be driven just to exercise `verify_consistency`.
+    fn complete_master_with_share(p: BigInt, secret: BigInt) -> SecretShareMasterCore<Complete, P256Params> {
+        let (enc_key, dec_key) = Paillier::keypair().keys();
+        SecretShareMasterCore {
+            p,
+            state: Complete { secret: SecretBigInt::new(secret) },
+            enc_key,
+            dec_key: SecretDecryptionKey::new(dec_key),
+            curve: PhantomData,
+        }
+    }
+
+    #[test]
+    fn verify_consistency_accepts_matching_shares_and_rejects_mismatched_ones() {
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
+
+        let point = sample_p256_point(&mut ChaChaRng::seed_from_u64(21));
+        let p = P256Params::prime();
+        let actual_x = BigInt::from_bytes(P256Params::x_bytes(&point)) % &p;
+
+        let slave_share = BigInt::from(123_456);
+        let master_share = BigInt::mod_sub(&actual_x, &slave_share, &p);
+        let master = complete_master_with_share(p, master_share);
+
+        assert!(master.verify_consistency(&slave_share, &point).is_ok());
+
+        let wrong_share = &slave_share + BigInt::from(1);
+        assert!(master.verify_consistency(&wrong_share, &point).is_err());
     }
 }